@@ -2,34 +2,98 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_types::transaction::ScriptABI;
-use std::{ffi::OsStr, fs, io::Read, path::Path};
+use std::{collections::HashSet, ffi::OsStr, fs, io::Read, path::Path};
 
 /// Support for code-generation in Rust.
 pub mod rust;
 
+/// Support for code-generation in TypeScript.
+pub mod typescript;
+
 /// Internals shared between languages.
 mod common;
 
-fn get_abi_paths(dir: &Path) -> std::io::Result<Vec<String>> {
+/// Chooses which `.abi` files `read_abis` picks up, so callers targeting custom Move packages
+/// can opt specific modules in or out instead of patching this crate.
+///
+/// `allow` and `deny` match against the module-name path component (e.g. `Genesis` matches any
+/// ABI under a `.../Genesis/...` directory); `deny` takes precedence over `allow`. An empty
+/// `allow` set means "every module not denied". `path_glob`, if set, is an additional glob
+/// applied to the full ABI path that a file must match; a pattern that fails to parse is
+/// ignored and matches every path.
+#[derive(Clone, Debug)]
+pub struct AbiFilter {
+    allow: HashSet<String>,
+    deny: HashSet<String>,
+    path_glob: Option<String>,
+}
+
+impl AbiFilter {
+    /// An unrestricted filter that allows every module; build it up with `allow_modules`,
+    /// `deny_modules`, and `with_path_glob`.
+    pub fn new() -> Self {
+        AbiFilter {
+            allow: HashSet::new(),
+            deny: HashSet::new(),
+            path_glob: None,
+        }
+    }
+
+    pub fn allow_modules(mut self, modules: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.allow.extend(modules.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn deny_modules(mut self, modules: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.deny.extend(modules.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_path_glob(mut self, glob: impl Into<String>) -> Self {
+        self.path_glob = Some(glob.into());
+        self
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        let module_allowed = self.allow.is_empty()
+            || self
+                .allow
+                .iter()
+                .any(|module| path.contains(&format!("/{}/", module)));
+        let module_denied = self
+            .deny
+            .iter()
+            .any(|module| path.contains(&format!("/{}/", module)));
+        let glob_matches = match &self.path_glob {
+            Some(glob) => glob::Pattern::new(glob)
+                .map(|pattern| pattern.matches(path))
+                .unwrap_or(true),
+            None => true,
+        };
+        module_allowed && !module_denied && glob_matches
+    }
+}
+
+/// The historical hardcoded exclusions (script builders can't handle these modules), kept as
+/// the default so existing callers see no behavior change when they don't configure a filter.
+impl Default for AbiFilter {
+    fn default() -> Self {
+        Self::new().deny_modules(["Genesis", "Coin", "ManagedCoin"])
+    }
+}
+
+fn get_abi_paths(dir: &Path, filter: &AbiFilter) -> std::io::Result<Vec<String>> {
     let mut abi_paths = Vec::new();
     if dir.is_dir() {
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
             if path.is_dir() {
-                abi_paths.append(&mut get_abi_paths(&path)?);
+                abi_paths.append(&mut get_abi_paths(&path, filter)?);
             } else if let Some("abi") = path.extension().and_then(OsStr::to_str) {
-                // not read Genesis abi (script builder doesn't work with the script function there)
-                if !path
-                    .to_str()
-                    .map(|s| {
-                        s.contains("/Genesis/")
-                            || s.contains("/Coin/")
-                            || s.contains("/ManagedCoin/")
-                    })
-                    .unwrap()
-                {
-                    abi_paths.push(path.to_str().unwrap().to_string());
+                let path = path.to_str().unwrap().to_string();
+                if filter.matches(&path) {
+                    abi_paths.push(path);
                 }
             }
         }
@@ -37,11 +101,22 @@ fn get_abi_paths(dir: &Path) -> std::io::Result<Vec<String>> {
     Ok(abi_paths)
 }
 
-/// Read all ABI files the specified directories. This supports both new and old `ScriptABI`s.
+/// Read all ABI files in the specified directories, applying the historical default exclusions
+/// (see [`AbiFilter::default`]). Existing callers keep building with no source changes; use
+/// [`read_abis_with_filter`] to customize which modules are picked up.
 pub fn read_abis(dir_paths: &[impl AsRef<Path>]) -> anyhow::Result<Vec<ScriptABI>> {
+    read_abis_with_filter(dir_paths, &AbiFilter::default())
+}
+
+/// Read all ABI files in the specified directories matching `filter`. This supports both new
+/// and old `ScriptABI`s.
+pub fn read_abis_with_filter(
+    dir_paths: &[impl AsRef<Path>],
+    filter: &AbiFilter,
+) -> anyhow::Result<Vec<ScriptABI>> {
     let mut abis = Vec::<ScriptABI>::new();
     for dir in dir_paths.iter() {
-        for path in get_abi_paths(dir.as_ref())? {
+        for path in get_abi_paths(dir.as_ref(), filter)? {
             let mut buffer = Vec::new();
             let mut f = std::fs::File::open(path)?;
             f.read_to_end(&mut buffer)?;
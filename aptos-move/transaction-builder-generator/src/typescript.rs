@@ -0,0 +1,213 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Code generation for TypeScript transaction builders.
+//!
+//! For each `ScriptABI::ScriptFunction` this emits a typed TS function that takes the
+//! function's arguments (plus any type arguments), BCS-serializes them, and returns an
+//! entry-function `TransactionPayload` ready to be signed and submitted by a frontend using
+//! the Aptos TS SDK.
+
+use crate::SourceInstaller;
+use aptos_types::transaction::{ArgumentABI, ScriptABI, TypeArgumentABI};
+use aptos_sdk::move_types::language_storage::TypeTag;
+use heck::{CamelCase, MixedCase};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Installer for generating TypeScript transaction builders from ABIs.
+pub struct Installer {
+    install_dir: PathBuf,
+}
+
+impl Installer {
+    pub fn new(install_dir: PathBuf) -> Self {
+        Installer { install_dir }
+    }
+
+    fn output_file_path(&self, name: &str) -> PathBuf {
+        self.install_dir.join(format!("{}.ts", name))
+    }
+}
+
+impl SourceInstaller for Installer {
+    type Error = std::io::Error;
+
+    fn install_transaction_builders(
+        &self,
+        name: &str,
+        abis: &[ScriptABI],
+    ) -> std::result::Result<(), Self::Error> {
+        fs::create_dir_all(&self.install_dir)?;
+        let mut file = fs::File::create(self.output_file_path(name))?;
+        write_header(&mut file)?;
+
+        for abi in abis {
+            if let ScriptABI::ScriptFunction(abi) = abi {
+                write_script_function(&mut file, abi)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_header(out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "// Copyright (c) Aptos\n\
+         // SPDX-License-Identifier: Apache-2.0\n\
+         //\n\
+         // This file was generated. Do not modify.\n\
+         //\n\
+         // @generated by aptos-move/transaction-builder-generator\n"
+    )?;
+    writeln!(
+        out,
+        "import {{ BCS, TxnBuilderTypes }} from \"aptos\";\n\
+         const {{ TransactionPayload }} = TxnBuilderTypes;\n"
+    )
+}
+
+/// Render one exported builder function for a Move script function ABI, e.g.:
+///
+/// ```ts
+/// export function encodeTransferScript(coinType: TxnBuilderTypes.TypeTag, to: TxnBuilderTypes.AccountAddress, amount: bigint, ): TransactionPayload {
+///   const typeArgs = [coinType];
+///   ...
+/// }
+/// ```
+fn write_script_function(
+    out: &mut impl Write,
+    abi: &aptos_types::transaction::ScriptFunctionABI,
+) -> std::io::Result<()> {
+    let function_name = format!("encode{}Script", abi.name().to_camel_case());
+
+    writeln!(out, "/**")?;
+    for line in abi.doc().lines() {
+        writeln!(out, " * {}", line)?;
+    }
+    writeln!(out, " */")?;
+
+    write!(out, "export function {}(", function_name)?;
+    for ty_arg in abi.ty_args() {
+        write!(out, "{}: TxnBuilderTypes.TypeTag, ", ty_arg_param_name(ty_arg))?;
+    }
+    for arg in abi.args() {
+        write!(
+            out,
+            "{}: {}, ",
+            arg.name().to_mixed_case(),
+            quote_ts_type(arg.type_tag())
+        )?;
+    }
+    writeln!(out, "): TransactionPayload {{")?;
+
+    writeln!(
+        out,
+        "  const typeArgs = [{}];",
+        abi.ty_args()
+            .iter()
+            .map(ty_arg_param_name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )?;
+    writeln!(out, "  const args = [")?;
+    for arg in abi.args() {
+        writeln!(
+            out,
+            "    {},",
+            serialize_arg_expr(arg.name().to_mixed_case().as_str(), arg.type_tag())
+        )?;
+    }
+    writeln!(out, "  ];")?;
+    writeln!(
+        out,
+        "  return new TxnBuilderTypes.TransactionPayloadEntryFunction(\n\
+         \x20\x20\x20\x20TxnBuilderTypes.EntryFunction.natural(\n\
+         \x20\x20\x20\x20\x20\x20\"{}::{}\",\n\
+         \x20\x20\x20\x20\x20\x20\"{}\",\n\
+         \x20\x20\x20\x20\x20\x20typeArgs,\n\
+         \x20\x20\x20\x20\x20\x20args,\n\
+         \x20\x20\x20\x20),\n\
+         \x20\x20);",
+        abi.module_name().address(),
+        abi.module_name().name(),
+        abi.name(),
+    )?;
+    writeln!(out, "}}\n")
+}
+
+fn ty_arg_param_name(ty_arg: &TypeArgumentABI) -> String {
+    ty_arg.name().to_mixed_case()
+}
+
+/// Map a Move `TypeTag` to the TS type a caller passes in. Recurses into `Vector` so e.g.
+/// `vector<u64>` types as `bigint[]` rather than the byte-vector special case.
+fn quote_ts_type(type_tag: &TypeTag) -> String {
+    use TypeTag::*;
+    match type_tag {
+        Bool => "boolean".to_string(),
+        U8 => "number".to_string(),
+        U64 | U128 => "bigint".to_string(),
+        Address => "TxnBuilderTypes.AccountAddress".to_string(),
+        Vector(inner) if matches!(**inner, U8) => "Uint8Array".to_string(),
+        Vector(inner) => format!("{}[]", quote_ts_type(inner)),
+        Struct(_) | Signer => "Uint8Array".to_string(),
+    }
+}
+
+/// Render the BCS-serializing expression for a single argument.
+fn serialize_arg_expr(param_name: &str, type_tag: &TypeTag) -> String {
+    use TypeTag::*;
+    match type_tag {
+        Bool => format!("BCS.bcsSerializeBool({})", param_name),
+        U8 => format!("BCS.bcsSerializeU8({})", param_name),
+        U64 => format!("BCS.bcsSerializeUint64({})", param_name),
+        U128 => format!("BCS.bcsSerializeU128({})", param_name),
+        Address => format!("BCS.bcsToBytes({})", param_name),
+        Vector(inner) if matches!(**inner, U8) => format!("BCS.bcsSerializeBytes({})", param_name),
+        // A non-byte vector has no single BCS helper: each element needs its own serializer
+        // call, so build one up by hand instead of falling back to the byte-vector helpers
+        // (which would pass the raw JS array straight to `bcsToBytes`, which expects a
+        // `Serializable`, not an array).
+        Vector(inner) => format!(
+            "(() => {{ const serializer = new BCS.Serializer(); {} return serializer.getBytes(); }})()",
+            serialize_into_expr("serializer", param_name, type_tag, inner.as_ref())
+        ),
+        Struct(_) | Signer => format!("BCS.bcsToBytes({})", param_name),
+    }
+}
+
+/// Render statements serializing `expr` (of Move type `type_tag`, a `Vector(element_type)`)
+/// into the already-constructed `serializer_var`, recursing for nested vectors.
+fn serialize_into_expr(
+    serializer_var: &str,
+    expr: &str,
+    type_tag: &TypeTag,
+    element_type: &TypeTag,
+) -> String {
+    use TypeTag::*;
+    debug_assert!(matches!(type_tag, Vector(_)));
+
+    let serialize_item = match element_type {
+        Bool => "serializer.serializeBool(item);".to_string(),
+        U8 => "serializer.serializeU8(item);".to_string(),
+        U64 => "serializer.serializeU64(item);".to_string(),
+        U128 => "serializer.serializeU128(item);".to_string(),
+        Address => "item.serialize(serializer);".to_string(),
+        Vector(nested) => serialize_into_expr("serializer", "item", element_type, nested),
+        Struct(_) | Signer => "serializer.serializeBytes(BCS.bcsToBytes(item));".to_string(),
+    };
+
+    format!(
+        "{serializer}.serializeU32AsUleb128(({expr}).length); \
+         ({expr}).forEach((item: {item_ty}) => {{ {serialize_item} }});",
+        serializer = serializer_var,
+        expr = expr,
+        item_ty = quote_ts_type(element_type),
+        serialize_item = serialize_item,
+    )
+}
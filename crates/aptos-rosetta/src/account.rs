@@ -12,6 +12,8 @@ use crate::{
         with_context,
     },
     error::{ApiError, ApiResult},
+    metering::with_metering,
+    price_oracle::PriceOracle,
     types::{
         coin_identifier, coin_store_identifier, AccountBalanceRequest, AccountBalanceResponse,
         Amount, BlockIdentifier, Currency, CurrencyMetadata,
@@ -21,7 +23,7 @@ use crate::{
 use aptos_logger::{debug, trace};
 use aptos_rest_client::{
     aptos::{Balance, TestCoin},
-    aptos_api_types::U64,
+    aptos_api_types::{ViewRequest, U64},
 };
 use aptos_sdk::move_types::language_storage::TypeTag;
 use aptos_types::account_address::AccountAddress;
@@ -29,10 +31,16 @@ use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
+    str::FromStr,
     sync::{Arc, RwLock},
 };
 use warp::Filter;
 
+/// Sub-account identifier for the native staking pool, holding a validator's own stake
+const STAKE_SUB_ACCOUNT: &str = "stake";
+/// Sub-account identifier for stake delegated to a validator through a delegation pool
+const DELEGATION_SUB_ACCOUNT: &str = "delegation";
+
 /// Account routes e.g. balance
 pub fn routes(
     server_context: RosettaContext,
@@ -40,6 +48,7 @@ pub fn routes(
     warp::post().and(
         warp::path!("account" / "balance")
             .and(warp::body::json())
+            .and(with_metering(server_context.clone()))
             .and(with_context(server_context))
             .and_then(handle_request(account_balance)),
     )
@@ -76,21 +85,32 @@ async fn account_balance(
         .await?;
     let balance_version = block_info.end_version;
 
-    let balances = get_balances(
-        &rest_client,
-        request.account_identifier.account_address()?,
-        balance_version,
-    )
-    .await?;
-
-    let amounts = convert_balances_to_amounts(
-        &rest_client,
-        server_context.coin_cache.clone(),
-        request.currencies,
-        balances,
-        balance_version,
-    )
-    .await?;
+    let address = request.account_identifier.account_address()?;
+
+    // A sub-account identifier such as `stake` or `delegation` requests a breakdown of
+    // locked staking balances rather than the liquid `CoinStore` balances
+    let amounts = if let Some(sub_account) = request.account_identifier.sub_account.as_ref() {
+        get_staking_balances(&rest_client, address, sub_account, balance_version).await?
+    } else {
+        let balances = get_balances(&rest_client, address, balance_version).await?;
+
+        // Only attempt valuation if the caller asked for a reference coin and an oracle is
+        // actually configured on this server; a missing oracle silently yields no valuation.
+        let valuation = request
+            .value_in_currency
+            .clone()
+            .zip(server_context.price_oracle.clone());
+
+        convert_balances_to_amounts(
+            &rest_client,
+            server_context.coin_cache.clone(),
+            request.currencies,
+            balances,
+            balance_version,
+            valuation,
+        )
+        .await?
+    };
 
     // Get the block identifier
     let block_identifier = BlockIdentifier::from_block_info(block_info);
@@ -102,21 +122,42 @@ async fn account_balance(
 }
 
 /// Lookup currencies and convert them to Rosetta types
+///
+/// `valuation`, if present, is a reference coin paired with the `PriceOracle` to price against
+/// it; each amount's currency metadata is annotated with its value in that reference coin,
+/// using a spot price read at the same `balance_version` as the balance itself.
 async fn convert_balances_to_amounts(
     rest_client: &aptos_rest_client::Client,
     coin_cache: Arc<CoinCache>,
     maybe_filter_currencies: Option<Vec<Currency>>,
     balances: HashMap<TypeTag, Balance>,
     balance_version: u64,
+    valuation: Option<(TypeTag, Arc<PriceOracle>)>,
 ) -> ApiResult<Vec<Amount>> {
     let mut amounts = Vec::new();
 
     // Lookup coins, and fill in currency codes
     for (coin, balance) in balances {
-        if let Some(currency) = coin_cache
-            .get_currency(rest_client, coin, Some(balance_version))
+        if let Some(mut currency) = coin_cache
+            .get_currency(rest_client, coin.clone(), Some(balance_version))
             .await?
         {
+            if let Some((reference_coin, oracle)) = valuation.as_ref() {
+                let reference_value = oracle
+                    .reference_value(
+                        rest_client,
+                        &coin_cache,
+                        &coin,
+                        reference_coin,
+                        balance_version,
+                    )
+                    .await?
+                    .map(|value| value.to_string());
+                if let Some(metadata) = currency.metadata.as_mut() {
+                    metadata.reference_value = reference_value;
+                }
+            }
+
             amounts.push(Amount {
                 value: balance.coin.value.0.to_string(),
                 currency,
@@ -192,6 +233,190 @@ async fn get_balances(
     }
 }
 
+/// Retrieve the locked staking balances for an account's stake pool or delegation pool
+///
+/// Unlike [`get_balances`], these amounts don't come from the `CoinStore` listing: they're read
+/// from the account's stake pool resource or from the delegation pool's `get_stake` view
+/// function, and split out as their own `active` / `inactive` / `pending_withdrawal`
+/// [`Amount`]s denominated in the native coin.
+async fn get_staking_balances(
+    rest_client: &aptos_rest_client::Client,
+    address: AccountAddress,
+    sub_account: &crate::types::SubAccountIdentifier,
+    version: u64,
+) -> ApiResult<Vec<Amount>> {
+    match sub_account.address.as_str() {
+        STAKE_SUB_ACCOUNT => get_stake_pool_balances(rest_client, address, version).await,
+        DELEGATION_SUB_ACCOUNT => {
+            get_delegation_pool_balances(rest_client, address, sub_account, version).await
+        },
+        other => Err(ApiError::DeserializationFailed(Some(format!(
+            "unsupported sub-account identifier `{}`",
+            other
+        )))),
+    }
+}
+
+/// Amounts locked in a validator's own stake pool at `0x1::Stake::StakePool`
+async fn get_stake_pool_balances(
+    rest_client: &aptos_rest_client::Client,
+    address: AccountAddress,
+    version: u64,
+) -> ApiResult<Vec<Amount>> {
+    /// On-chain representation of `0x1::Stake::StakePool`
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct StakePool {
+        active: Coin,
+        inactive: Coin,
+        pending_active: Coin,
+        pending_inactive: Coin,
+    }
+
+    let resource_tag = "0x1::Stake::StakePool";
+    let stake_pool = match get_resource_at_version::<StakePool>(
+        rest_client,
+        address,
+        resource_tag,
+        version,
+    )
+    .await?
+    {
+        Some(stake_pool) => stake_pool,
+        // No stake pool registered yet, there's simply nothing locked
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(vec![
+        staking_amount("active", stake_pool.active.value.0 + stake_pool.pending_active.value.0),
+        staking_amount("inactive", stake_pool.inactive.value.0),
+        staking_amount("pending_withdrawal", stake_pool.pending_inactive.value.0),
+    ])
+}
+
+/// Amounts delegated to a validator through a delegation pool.
+///
+/// A delegator's stake isn't stored as a resource on the delegator's own account — it's tracked
+/// as a share of the pool, which lives at the pool's own resource-account address. So unlike
+/// [`get_stake_pool_balances`], this can't be answered from `address` alone: the caller must
+/// supply that pool address via `sub_account.metadata.pool_address`, and the delegator's actual
+/// position is then read with the framework's `0x1::delegation_pool::get_stake` view function
+/// rather than guessed at from a resource layout.
+async fn get_delegation_pool_balances(
+    rest_client: &aptos_rest_client::Client,
+    address: AccountAddress,
+    sub_account: &crate::types::SubAccountIdentifier,
+    version: u64,
+) -> ApiResult<Vec<Amount>> {
+    let pool_address = delegation_pool_address(sub_account)?;
+
+    let view_request = ViewRequest {
+        function: "0x1::delegation_pool::get_stake"
+            .parse()
+            .map_err(|_| ApiError::InternalError(Some("invalid view function id".to_string())))?,
+        type_arguments: vec![],
+        arguments: vec![
+            serde_json::Value::String(pool_address.to_string()),
+            serde_json::Value::String(address.to_string()),
+        ],
+    };
+
+    let values = rest_client
+        .view(&view_request, Some(version))
+        .await?
+        .into_inner();
+
+    let stakes = values
+        .iter()
+        .map(|value| value.as_str().and_then(|value| value.parse::<u64>().ok()))
+        .collect::<Option<Vec<u64>>>()
+        .filter(|stakes| stakes.len() == 3)
+        .ok_or_else(|| {
+            ApiError::DeserializationFailed(Some(
+                "get_stake returned an unexpected shape".to_string(),
+            ))
+        })?;
+    let (active, inactive, pending_inactive) = (stakes[0], stakes[1], stakes[2]);
+
+    Ok(vec![
+        staking_amount("active", active),
+        staking_amount("inactive", inactive),
+        staking_amount("pending_withdrawal", pending_inactive),
+    ])
+}
+
+/// Resolve the delegation pool address a `delegation` sub-account request must carry in
+/// `metadata.pool_address`, since it can't be derived from the delegator's own address.
+fn delegation_pool_address(
+    sub_account: &crate::types::SubAccountIdentifier,
+) -> ApiResult<AccountAddress> {
+    let pool_address = sub_account
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get("pool_address"))
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| {
+            ApiError::DeserializationFailed(Some(
+                "sub-account `delegation` requires metadata.pool_address".to_string(),
+            ))
+        })?;
+
+    AccountAddress::from_str(pool_address).map_err(|_| {
+        ApiError::DeserializationFailed(Some(format!(
+            "invalid pool_address `{}`",
+            pool_address
+        )))
+    })
+}
+
+/// A bare `u64` coin amount, matching the shape of the Move `Coin<CoinType>` resource field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Coin {
+    value: U64,
+}
+
+/// Build an [`Amount`] of the native coin, tagged with which staking bucket it came from
+fn staking_amount(bucket: &str, value: u64) -> Amount {
+    Amount {
+        value: value.to_string(),
+        currency: Currency {
+            metadata: Some(CurrencyMetadata {
+                move_type: format!("{}::{}", native_coin().symbol, bucket),
+                reference_value: None,
+            }),
+            ..native_coin()
+        },
+    }
+}
+
+/// Fetch and deserialize a single account resource at a historical version, treating a missing
+/// resource as `None` rather than an error
+async fn get_resource_at_version<T: serde::de::DeserializeOwned>(
+    rest_client: &aptos_rest_client::Client,
+    address: AccountAddress,
+    resource_tag: &str,
+    version: u64,
+) -> ApiResult<Option<T>> {
+    const ENCODE_CHARS: &AsciiSet = &CONTROLS.add(b'<').add(b'>');
+    let encoded_resource_tag = utf8_percent_encode(resource_tag, ENCODE_CHARS).to_string();
+
+    let response = rest_client
+        .get_account_resource_at_version(address, &encoded_resource_tag, version)
+        .await?;
+
+    match response.into_inner() {
+        Some(resource) => {
+            let value = serde_json::from_value::<T>(resource.data).map_err(|_| {
+                ApiError::DeserializationFailed(Some(format!(
+                    "{} failed to deserialize",
+                    resource_tag
+                )))
+            })?;
+            Ok(Some(value))
+        },
+        None => Ok(None),
+    }
+}
+
 /// A cache for currencies, so we don't have to keep looking up the status of it
 #[derive(Debug)]
 pub struct CoinCache {
@@ -255,12 +480,11 @@ impl CoinCache {
             _ => return Ok(None),
         };
 
-        // Nested types are not supported for now
-        if !struct_tag.type_params.is_empty() {
-            return Ok(None);
-        }
-
-        // Retrieve the coin type
+        // Retrieve the coin type. `struct_tag`'s `Display` impl recurses through any nested
+        // type parameters (e.g. a wrapped coin `0x1::Wrapper::Wrapped<0x1::TestCoin::TestCoin>`),
+        // so this resolves generic and nested coins the same way as simple ones. The cache in
+        // `get_currency` is already keyed on the full `TypeTag`, so distinct instantiations of
+        // the same struct don't collide.
         const ENCODE_CHARS: &AsciiSet = &CONTROLS.add(b'<').add(b'>');
         let address = struct_tag.address;
         let resource_tag = format!("0x1::Coin::CoinInfo<{}>", struct_tag);
@@ -276,7 +500,9 @@ impl CoinCache {
                 .await?
         };
 
-        // At this point if we've retrieved it and it's bad, we error out
+        // If the resource came back malformed, that's a real error. If `CoinInfo` genuinely
+        // doesn't exist for this type (e.g. a generic coin that was never registered), the coin
+        // is simply not a valid currency and we resolve to `None` rather than failing the request.
         if let Some(resource) = response.into_inner() {
             let coin_info = serde_json::from_value::<CoinInfo>(resource.data).map_err(|_| {
                 ApiError::DeserializationFailed(Some(format!(
@@ -290,13 +516,11 @@ impl CoinCache {
                 decimals: coin_info.decimals.0,
                 metadata: Some(CurrencyMetadata {
                     move_type: resource_tag.to_string(),
+                    reference_value: None,
                 }),
             }))
         } else {
-            Err(ApiError::DeserializationFailed(Some(format!(
-                "Currency {} not found",
-                coin
-            ))))
+            Ok(None)
         }
     }
 }
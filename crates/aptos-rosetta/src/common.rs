@@ -0,0 +1,145 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Shared helpers used by every Rosetta API route module.
+
+use crate::{
+    error::{ApiError, ApiResult},
+    types::{Currency, CurrencyMetadata, NetworkIdentifier, PartialBlockIdentifier},
+    RosettaContext,
+};
+use aptos_sdk::move_types::{
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+};
+use aptos_types::account_address::AccountAddress;
+use futures::future::BoxFuture;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{convert::Infallible, future::Future, str::FromStr};
+use warp::{Filter, Rejection, Reply};
+
+/// The native coin (`APT`) as a Rosetta [`Currency`]
+pub fn native_coin() -> Currency {
+    Currency {
+        symbol: "APT".to_string(),
+        decimals: 8,
+        metadata: Some(CurrencyMetadata {
+            move_type: format!("0x1::Coin::CoinStore<{}>", native_coin_tag()),
+            reference_value: None,
+        }),
+    }
+}
+
+/// The Move type of the native coin
+pub fn native_coin_tag() -> TypeTag {
+    TypeTag::Struct(Box::new(StructTag {
+        address: AccountAddress::ONE,
+        module: Identifier::new("TestCoin").unwrap(),
+        name: Identifier::new("TestCoin").unwrap(),
+        type_params: vec![],
+    }))
+}
+
+/// Ensure the caller's requested network matches this server's configured network
+pub fn check_network(
+    network_identifier: NetworkIdentifier,
+    server_context: &RosettaContext,
+) -> ApiResult<()> {
+    if network_identifier.blockchain != "aptos" || network_identifier.network != server_context.network
+    {
+        return Err(ApiError::NetworkNotSupported(Some(format!(
+            "unsupported network {}:{}",
+            network_identifier.blockchain, network_identifier.network
+        ))));
+    }
+    Ok(())
+}
+
+/// Resolve a (possibly partial) block identifier from a request into a concrete block index,
+/// defaulting to the latest known block when neither `index` nor `hash` was supplied
+pub async fn get_block_index_from_request(
+    server_context: &RosettaContext,
+    block_identifier: PartialBlockIdentifier,
+) -> ApiResult<u64> {
+    let block_cache = server_context.block_cache()?;
+    if let Some(index) = block_identifier.index {
+        Ok(index)
+    } else if let Some(hash) = block_identifier.hash {
+        block_cache.get_block_index_by_hash(&hash).await
+    } else {
+        block_cache.get_latest_block_index().await
+    }
+}
+
+/// A minimal resolved block, just enough to key a balance lookup
+#[derive(Clone, Debug)]
+pub struct BlockInfo {
+    pub index: u64,
+    pub hash: String,
+    pub end_version: u64,
+}
+
+/// Resolves block indices/hashes to the account state version at the end of that block
+#[derive(Debug, Default)]
+pub struct BlockCache {}
+
+impl BlockCache {
+    pub async fn get_block_info(&self, index: u64) -> ApiResult<BlockInfo> {
+        Ok(BlockInfo {
+            index,
+            hash: index.to_string(),
+            end_version: index,
+        })
+    }
+
+    pub async fn get_latest_block_index(&self) -> ApiResult<u64> {
+        Ok(0)
+    }
+
+    pub async fn get_block_index_by_hash(&self, hash: &str) -> ApiResult<u64> {
+        u64::from_str(hash)
+            .map_err(|_| ApiError::DeserializationFailed(Some(format!("invalid block hash `{}`", hash))))
+    }
+}
+
+/// A warp filter that injects a clone of the server's [`RosettaContext`] into the route
+pub fn with_context(
+    context: RosettaContext,
+) -> impl Filter<Extract = (RosettaContext,), Error = Infallible> + Clone {
+    warp::any().map(move || context.clone())
+}
+
+/// Wrap an `async fn(Req, RosettaContext) -> ApiResult<Resp>` handler into the
+/// `Fn(..) -> Future<Result<impl Reply, Rejection>>` shape `and_then` expects, converting
+/// `ApiError`s into warp rejections carrying the Rosetta error body.
+pub fn handle_request<F, Fut, Req, Resp>(
+    handler: F,
+) -> impl Fn(Req, RosettaContext) -> BoxFuture<'static, Result<warp::reply::Json, Rejection>> + Clone
+where
+    F: Fn(Req, RosettaContext) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = ApiResult<Resp>> + Send + 'static,
+    Req: DeserializeOwned + Send + 'static,
+    Resp: Serialize,
+{
+    move |request: Req, context: RosettaContext| {
+        let handler = handler.clone();
+        Box::pin(async move {
+            match handler(request, context).await {
+                Ok(response) => Ok(warp::reply::json(&response)),
+                Err(err) => Err(warp::reject::custom(err)),
+            }
+        })
+    }
+}
+
+/// Convert a rejected `ApiError` (e.g. from `with_metering`, or from `handle_request`) into its
+/// Rosetta error reply
+pub async fn handle_rejection(rejection: Rejection) -> Result<Box<dyn Reply>, Infallible> {
+    if let Some(err) = rejection.find::<ApiError>() {
+        Ok(Box::new(err.clone()))
+    } else {
+        Ok(Box::new(ApiError::InternalError(Some(
+            "unhandled rejection".to_string(),
+        ))))
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Error type for the Rosetta API, mapped onto the Rosetta `Error` object on the wire.
+//!
+//! [API Spec](https://www.rosetta-api.org/docs/api_objects.html#error)
+
+use serde::{Deserialize, Serialize};
+use warp::{http::StatusCode, reject::Reject, Reply};
+
+/// Convenience alias for handlers that can fail with an [`ApiError`]
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// Errors returned by the Rosetta API, serialized as a Rosetta `Error` object
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ApiError {
+    /// The requested network isn't supported by this server
+    NetworkNotSupported(Option<String>),
+    /// A request field or an on-chain resource couldn't be parsed into the type we expected
+    DeserializationFailed(Option<String>),
+    /// The caller exceeded its request quota; carries a human-readable retry hint
+    RateLimited(Option<String>),
+    /// Catch-all for failures surfaced from the REST client or other internal errors
+    InternalError(Option<String>),
+}
+
+impl ApiError {
+    fn code(&self) -> u32 {
+        match self {
+            ApiError::NetworkNotSupported(_) => 1,
+            ApiError::DeserializationFailed(_) => 2,
+            ApiError::RateLimited(_) => 3,
+            ApiError::InternalError(_) => 4,
+        }
+    }
+
+    fn retriable(&self) -> bool {
+        matches!(self, ApiError::RateLimited(_) | ApiError::InternalError(_))
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::NetworkNotSupported(msg)
+            | ApiError::DeserializationFailed(msg)
+            | ApiError::RateLimited(msg)
+            | ApiError::InternalError(msg) => {
+                msg.clone().unwrap_or_else(|| "unknown error".to_string())
+            },
+        }
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::NetworkNotSupported(_) | ApiError::DeserializationFailed(_) => {
+                StatusCode::BAD_REQUEST
+            },
+        }
+    }
+}
+
+/// Wire representation of a Rosetta `Error` object
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ErrorResponse {
+    code: u32,
+    message: String,
+    retriable: bool,
+}
+
+impl Reject for ApiError {}
+
+impl Reply for ApiError {
+    fn into_response(self) -> warp::reply::Response {
+        warp::reply::with_status(
+            warp::reply::json(&ErrorResponse {
+                code: self.code(),
+                message: self.message(),
+                retriable: self.retriable(),
+            }),
+            self.status_code(),
+        )
+        .into_response()
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        ApiError::InternalError(Some(err.to_string()))
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! An implementation of the [Rosetta API](https://www.rosetta-api.org/docs/Reference.html) for
+//! the Aptos blockchain.
+
+pub mod account;
+pub mod common;
+pub mod error;
+pub mod metering;
+pub mod price_oracle;
+pub mod types;
+
+use crate::{
+    common::{handle_rejection, BlockCache},
+    error::{ApiError, ApiResult},
+    metering::RequestMeter,
+    price_oracle::PriceOracle,
+};
+use aptos_rest_client::Client;
+use std::sync::Arc;
+use warp::Filter;
+
+pub use account::CoinCache;
+
+/// Shared state handed to every route handler
+#[derive(Clone)]
+pub struct RosettaContext {
+    /// Client for the node's REST API, if this server is connected to one
+    rest_client: Option<Client>,
+    /// The Rosetta network name this server serves (e.g. `mainnet`)
+    network: String,
+    /// Resolves block indices/hashes to account-state versions
+    block_cache: Option<Arc<BlockCache>>,
+    /// Cache of on-chain `CoinInfo` lookups, shared across requests
+    pub coin_cache: Arc<CoinCache>,
+    /// Optional DEX-backed reference-currency valuation, see [`PriceOracle`]
+    pub price_oracle: Option<Arc<PriceOracle>>,
+    /// Per-client request metering and quotas, see [`RequestMeter`]
+    pub metering: Arc<RequestMeter>,
+}
+
+impl std::fmt::Debug for RosettaContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RosettaContext")
+            .field("network", &self.network)
+            .finish()
+    }
+}
+
+impl RosettaContext {
+    pub fn new(network: String, rest_client: Option<Client>) -> Self {
+        Self {
+            rest_client,
+            network,
+            block_cache: Some(Arc::new(BlockCache::default())),
+            coin_cache: Arc::new(CoinCache::new()),
+            price_oracle: None,
+            metering: Arc::new(RequestMeter::new(100.0, 10.0)),
+        }
+    }
+
+    /// Configure the optional reference-currency price oracle
+    pub fn with_price_oracle(mut self, price_oracle: PriceOracle) -> Self {
+        self.price_oracle = Some(Arc::new(price_oracle));
+        self
+    }
+
+    /// Configure request metering (bucket `capacity`, steady-state `refill_per_sec`)
+    pub fn with_metering(mut self, capacity: f64, refill_per_sec: f64) -> Self {
+        self.metering = Arc::new(RequestMeter::new(capacity, refill_per_sec));
+        self
+    }
+
+    pub fn rest_client(&self) -> ApiResult<Client> {
+        self.rest_client
+            .clone()
+            .ok_or_else(|| ApiError::InternalError(Some("no REST client configured".to_string())))
+    }
+
+    pub fn block_cache(&self) -> ApiResult<Arc<BlockCache>> {
+        self.block_cache
+            .clone()
+            .ok_or_else(|| ApiError::InternalError(Some("no block cache configured".to_string())))
+    }
+}
+
+/// All Rosetta API routes served by this crate
+pub fn routes(
+    server_context: RosettaContext,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    account::routes(server_context.clone())
+        .or(metering::routes(server_context))
+        .recover(handle_rejection)
+}
@@ -0,0 +1,160 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-client request metering and quotas for the Rosetta server.
+//!
+//! Tracks request counts and a refillable token-bucket allowance per client identity (the
+//! `x-api-key` header if present, otherwise the request's source address), so hosted
+//! deployments can cap abusive polling of read endpoints like `/account/balance` without a
+//! separate reverse proxy in front of the server. Registered on `RosettaContext` as `metering`
+//! (see `lib.rs`), alongside `coin_cache` and `price_oracle`.
+
+use crate::{common::with_context, error::ApiError};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+use warp::Filter;
+
+/// Header carrying an API key identifying the caller, if the operator requires one.
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Per-client request counters and token-bucket allowance.
+#[derive(Debug)]
+struct ClientUsage {
+    /// Requests accepted from this client since its entry was created, for export/monitoring.
+    /// The count restarts from zero if the entry is pruned after `idle_ttl`.
+    request_count: u64,
+    /// Tokens currently available to spend.
+    tokens: f64,
+    /// When `tokens` was last topped up.
+    last_refill: Instant,
+}
+
+/// A snapshot of one client's accumulated counters, for export to an operator's metrics system.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientUsageSnapshot {
+    pub client_id: String,
+    pub request_count: u64,
+}
+
+/// Entries idle longer than this are evicted, so tracking clients can't grow the map forever
+const DEFAULT_IDLE_TTL: Duration = Duration::from_secs(3600);
+
+/// Tracks and caps per-client request usage with a refillable token bucket.
+#[derive(Debug)]
+pub struct RequestMeter {
+    /// Bucket size, i.e. the largest burst a client can spend at once.
+    capacity: f64,
+    /// Steady-state allowed rate, in requests per second.
+    refill_per_sec: f64,
+    /// Clients that haven't made a request in this long are pruned from `usage`.
+    idle_ttl: Duration,
+    usage: RwLock<HashMap<String, ClientUsage>>,
+}
+
+impl RequestMeter {
+    pub fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self::with_idle_ttl(capacity, refill_per_sec, DEFAULT_IDLE_TTL)
+    }
+
+    pub fn with_idle_ttl(capacity: f64, refill_per_sec: f64, idle_ttl: Duration) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+            idle_ttl,
+            usage: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Spend one token for `client_id`, topping up the bucket first. On failure, returns how
+    /// long the client should wait before retrying.
+    fn check(&self, client_id: &str) -> Result<(), Duration> {
+        let mut usage = self.usage.write().unwrap();
+        let now = Instant::now();
+
+        // Prune clients that have been idle past the TTL, so a growing set of distinct clients
+        // (e.g. source IPs) doesn't leak memory indefinitely.
+        let idle_ttl = self.idle_ttl;
+        usage.retain(|id, entry| id == client_id || now.duration_since(entry.last_refill) < idle_ttl);
+
+        let entry = usage.entry(client_id.to_string()).or_insert_with(|| ClientUsage {
+            request_count: 0,
+            tokens: self.capacity,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(entry.last_refill).as_secs_f64();
+        entry.tokens = (entry.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        entry.last_refill = now;
+
+        if entry.tokens < 1.0 {
+            let shortfall = 1.0 - entry.tokens;
+            return Err(Duration::from_secs_f64(shortfall / self.refill_per_sec));
+        }
+
+        entry.tokens -= 1.0;
+        entry.request_count += 1;
+        Ok(())
+    }
+
+    /// Export the accumulated per-client counters, e.g. for a metrics endpoint.
+    pub fn snapshot(&self) -> Vec<ClientUsageSnapshot> {
+        self.usage
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(client_id, usage)| ClientUsageSnapshot {
+                client_id: client_id.clone(),
+                request_count: usage.request_count,
+            })
+            .collect()
+    }
+}
+
+/// Metering routes, e.g. exporting per-client usage counters for an operator's metrics system.
+pub fn routes(
+    server_context: crate::RosettaContext,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get().and(
+        warp::path!("metering" / "snapshot")
+            .and(with_context(server_context))
+            .map(|server_context: crate::RosettaContext| {
+                warp::reply::json(&server_context.metering.snapshot())
+            }),
+    )
+}
+
+/// Identify the caller by API key if one was supplied, otherwise by source address.
+fn client_identity(api_key: Option<String>, remote: Option<SocketAddr>) -> String {
+    api_key.unwrap_or_else(|| {
+        remote
+            .map(|addr| addr.ip().to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    })
+}
+
+/// A warp filter enforcing `server_context`'s request quota before the request reaches its
+/// handler, rejecting over-quota requests with a Rosetta [`ApiError`] carrying a retry hint.
+pub fn with_metering(
+    server_context: crate::RosettaContext,
+) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>(API_KEY_HEADER)
+        .and(warp::filters::addr::remote())
+        .and_then(move |api_key: Option<String>, remote: Option<SocketAddr>| {
+            let server_context = server_context.clone();
+            async move {
+                let client_id = client_identity(api_key, remote);
+                match server_context.metering.check(&client_id) {
+                    Ok(()) => Ok(()),
+                    Err(retry_after) => Err(warp::reject::custom(ApiError::RateLimited(Some(
+                        format!("retry after {}s", retry_after.as_secs().max(1)),
+                    )))),
+                }
+            }
+        })
+        .untuple_one()
+}
@@ -0,0 +1,171 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! On-chain DEX spot-price oracle for valuing account balances in a reference coin.
+//!
+//! Registered on `RosettaContext` as `price_oracle` (see `lib.rs`), alongside `coin_cache`.
+//!
+//! Each configured [`LiquidityPool`] holds two `CoinStore` reserves (`r_base`, `r_quote`) whose
+//! constant-product spot price is `p = r_quote / r_base`, adjusted for each coin's `decimals`.
+//! A coin with no pool configured against the reference coin chains through the reference
+//! coin's own pool instead of erroring; if no path exists, or a pool's reserves are empty, the
+//! coin is simply left unvalued.
+
+use crate::{account::CoinCache, error::ApiResult};
+use aptos_rest_client::{aptos_api_types::U64, Client};
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_types::account_address::AccountAddress;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A configured DEX liquidity pool, pricing `base` against `quote` via its `CoinStore` reserves
+/// held at `pool_address`.
+#[derive(Clone, Debug)]
+pub struct LiquidityPool {
+    pub base: TypeTag,
+    pub quote: TypeTag,
+    pub pool_address: AccountAddress,
+}
+
+/// Reads configured DEX liquidity pools to value balances in a chosen reference coin.
+#[derive(Clone, Debug, Default)]
+pub struct PriceOracle {
+    /// Pools keyed by the coin they price.
+    pools: HashMap<TypeTag, LiquidityPool>,
+}
+
+impl PriceOracle {
+    pub fn new(pools: Vec<LiquidityPool>) -> Self {
+        Self {
+            pools: pools
+                .into_iter()
+                .map(|pool| (pool.base.clone(), pool))
+                .collect(),
+        }
+    }
+
+    /// Value one unit of `coin` in terms of `reference_coin` at `version`, or `None` if there's
+    /// no way to price it.
+    pub async fn reference_value(
+        &self,
+        rest_client: &Client,
+        coin_cache: &CoinCache,
+        coin: &TypeTag,
+        reference_coin: &TypeTag,
+        version: u64,
+    ) -> ApiResult<Option<f64>> {
+        if coin == reference_coin {
+            return Ok(Some(1.0));
+        }
+
+        let coin_pool = match self.pools.get(coin) {
+            Some(pool) => pool,
+            None => return Ok(None),
+        };
+
+        // The pool already quotes `coin` directly against the reference coin.
+        if &coin_pool.quote == reference_coin {
+            return self.spot_price(rest_client, coin_cache, coin, version).await;
+        }
+
+        // Otherwise chain through the reference coin's own pool — but only if it's quoted
+        // against the same hub currency as `coin`'s pool. Dividing two spot prices quoted
+        // against different hubs would produce a numerically meaningless ratio, so in that case
+        // (or if the reference coin has no pool at all) there's simply no valuation.
+        let reference_pool = match self.pools.get(reference_coin) {
+            Some(pool) => pool,
+            None => return Ok(None),
+        };
+        if coin_pool.quote != reference_pool.quote {
+            return Ok(None);
+        }
+
+        let coin_price = match self.spot_price(rest_client, coin_cache, coin, version).await? {
+            Some(price) => price,
+            None => return Ok(None),
+        };
+
+        match self
+            .spot_price(rest_client, coin_cache, reference_coin, version)
+            .await?
+        {
+            Some(reference_price) if reference_price > 0.0 => Ok(Some(coin_price / reference_price)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Constant-product spot price `p = r_quote / r_base` of the pool configured for `coin`,
+    /// adjusted for each side's decimals. `None` if no pool is configured, or its reserves are
+    /// empty.
+    async fn spot_price(
+        &self,
+        rest_client: &Client,
+        coin_cache: &CoinCache,
+        coin: &TypeTag,
+        version: u64,
+    ) -> ApiResult<Option<f64>> {
+        let pool = match self.pools.get(coin) {
+            Some(pool) => pool,
+            None => return Ok(None),
+        };
+
+        let r_base = get_reserve(rest_client, pool.pool_address, &pool.base, version).await?;
+        let r_quote = get_reserve(rest_client, pool.pool_address, &pool.quote, version).await?;
+        let (r_base, r_quote) = match (r_base, r_quote) {
+            (Some(base), Some(quote)) if base > 0 => (base, quote),
+            _ => return Ok(None),
+        };
+
+        let base_decimals = coin_decimals(rest_client, coin_cache, &pool.base, version).await?;
+        let quote_decimals = coin_decimals(rest_client, coin_cache, &pool.quote, version).await?;
+
+        let adjusted_base = r_base as f64 / 10f64.powi(base_decimals as i32);
+        let adjusted_quote = r_quote as f64 / 10f64.powi(quote_decimals as i32);
+
+        Ok(Some(adjusted_quote / adjusted_base))
+    }
+}
+
+async fn coin_decimals(
+    rest_client: &Client,
+    coin_cache: &CoinCache,
+    coin: &TypeTag,
+    version: u64,
+) -> ApiResult<u64> {
+    Ok(coin_cache
+        .get_currency(rest_client, coin.clone(), Some(version))
+        .await?
+        .map(|currency| currency.decimals)
+        .unwrap_or(0))
+}
+
+/// Read a pool's `CoinStore<T>` reserve at `version`, or `None` if it holds none.
+async fn get_reserve(
+    rest_client: &Client,
+    pool_address: AccountAddress,
+    coin: &TypeTag,
+    version: u64,
+) -> ApiResult<Option<u64>> {
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct CoinStoreValue {
+        coin: Reserve,
+    }
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Reserve {
+        value: U64,
+    }
+
+    const ENCODE_CHARS: &AsciiSet = &CONTROLS.add(b'<').add(b'>');
+    let resource_tag = format!("0x1::Coin::CoinStore<{}>", coin);
+    let encoded_resource_tag = utf8_percent_encode(&resource_tag, ENCODE_CHARS).to_string();
+
+    let response = rest_client
+        .get_account_resource_at_version(pool_address, &encoded_resource_tag, version)
+        .await?;
+
+    Ok(response
+        .into_inner()
+        .and_then(|resource| serde_json::from_value::<CoinStoreValue>(resource.data).ok())
+        .map(|value| value.coin.value.0))
+}
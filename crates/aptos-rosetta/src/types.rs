@@ -0,0 +1,145 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rosetta API request/response types.
+//!
+//! [API Spec](https://www.rosetta-api.org/docs/api_objects.html)
+
+use crate::error::{ApiError, ApiResult};
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Module name holding `CoinStore<T>` / `CoinInfo<T>`
+pub fn coin_identifier() -> String {
+    "Coin".to_string()
+}
+
+/// Resource name of `0x1::Coin::CoinStore<T>`
+pub fn coin_store_identifier() -> String {
+    "CoinStore".to_string()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkIdentifier {
+    pub blockchain: String,
+    pub network: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SubAccountIdentifier {
+    pub address: String,
+    /// Sub-account-specific data a caller must supply to resolve the balance. The `delegation`
+    /// sub-account uses this to carry `pool_address`, the delegation pool's own resource-account
+    /// address — delegated stake is tracked there, not on the delegator's account, so there's no
+    /// way to find it from `address` alone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountIdentifier {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub_account: Option<SubAccountIdentifier>,
+}
+
+impl AccountIdentifier {
+    pub fn account_address(&self) -> ApiResult<AccountAddress> {
+        AccountAddress::from_str(&self.address)
+            .map_err(|_| ApiError::DeserializationFailed(Some(format!(
+                "invalid account address `{}`",
+                self.address
+            ))))
+    }
+}
+
+/// A possibly-partial reference to a block, as supplied by a caller
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PartialBlockIdentifier {
+    pub index: Option<u64>,
+    pub hash: Option<String>,
+}
+
+/// A fully resolved reference to a block
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockIdentifier {
+    pub index: u64,
+    pub hash: String,
+}
+
+impl BlockIdentifier {
+    pub fn from_block_info(block_info: crate::common::BlockInfo) -> Self {
+        Self {
+            index: block_info.index,
+            hash: block_info.hash,
+        }
+    }
+}
+
+/// Currency-specific metadata carried alongside a [`Currency`]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CurrencyMetadata {
+    /// The fully-qualified Move type backing this currency, e.g. `0x1::Coin::CoinInfo<...>`; for
+    /// staking sub-account amounts, the native coin's symbol and the staking bucket instead, e.g.
+    /// `APT::active`
+    pub move_type: String,
+    /// This currency's value in the reference coin requested via
+    /// `AccountBalanceRequest::value_in_currency`, if valuation was requested and a price could
+    /// be computed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_value: Option<String>,
+}
+
+// `reference_value` is filled in per-request (or not at all, if no oracle is configured) and
+// must not affect equality or hashing: `account.rs::convert_balances_to_amounts` hashes the
+// caller's requested `Currency`s into a `HashSet` and looks up server-computed ones in it, and
+// those two `Currency`s only ever agree on `reference_value` by accident. Deriving `Eq`/`Hash`
+// over all fields made that lookup silently fail to match, so every requested balance came back
+// as zero whenever valuation was requested alongside a `currencies` filter.
+impl PartialEq for CurrencyMetadata {
+    fn eq(&self, other: &Self) -> bool {
+        self.move_type == other.move_type
+    }
+}
+
+impl Eq for CurrencyMetadata {}
+
+impl std::hash::Hash for CurrencyMetadata {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.move_type.hash(state);
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Currency {
+    pub symbol: String,
+    pub decimals: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CurrencyMetadata>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Amount {
+    pub value: String,
+    pub currency: Currency,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountBalanceRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub account_identifier: AccountIdentifier,
+    #[serde(default)]
+    pub block_identifier: PartialBlockIdentifier,
+    pub currencies: Option<Vec<Currency>>,
+    /// Reference coin to value balances in, read via a configured `PriceOracle`
+    #[serde(default)]
+    pub value_in_currency: Option<TypeTag>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountBalanceResponse {
+    pub block_identifier: BlockIdentifier,
+    pub balances: Vec<Amount>,
+}